@@ -0,0 +1,146 @@
+//! Compute the exact probability distribution of a sum of dice,
+//! without resorting to Monte-Carlo sampling.
+
+use std::collections::BTreeMap;
+
+use crate::{DiceExpr, RangeDie};
+
+/// The exact probability distribution of a dice expression's total,
+/// computed analytically rather than by Monte-Carlo sampling.
+///
+/// This complements the sampling APIs elsewhere in the crate: instead
+/// of rolling many times and looking at the spread of results, it
+/// answers questions like "what's the chance 3d6+2 beats 14?"
+/// directly. Build one either from a handful of `RangeDie`s (a plain
+/// sum), or from a [`DiceExpr`] via [`Distribution::from_expr`] when
+/// flat modifiers or keep/drop rules need to be taken into account.
+#[derive(Clone, Debug)]
+pub struct Distribution {
+    repr: Repr,
+}
+
+#[derive(Clone, Debug)]
+enum Repr {
+    Bounds(Vec<(i32, i32)>),
+    Expr(DiceExpr),
+}
+
+impl Distribution {
+    /// Build a distribution over the unmodified sum of the given dice,
+    /// reusing each die's face bounds. For expressions with flat
+    /// modifiers or keep/drop rules (e.g. `"3d6+2"`, `"2d20kh1"`), use
+    /// [`Distribution::from_expr`] with a parsed `DiceExpr` instead.
+    pub fn new(dice: &[RangeDie<i32>]) -> Self {
+        Distribution {
+            repr: Repr::Bounds(dice.iter().map(RangeDie::bounds).collect()),
+        }
+    }
+
+    /// Build a distribution from a parsed dice-notation expression
+    /// (see [`crate::parse`]), exactly accounting for any flat
+    /// modifiers, multipliers, and keep/drop rules it contains.
+    ///
+    /// ```
+    /// use rusty_dice::{parse, Distribution};
+    ///
+    /// let attack = parse("3d6+2").unwrap();
+    /// let odds = Distribution::from_expr(&attack).probability_at_least(14);
+    /// assert!((odds - 0.375).abs() < 1e-9);
+    /// ```
+    pub fn from_expr(expr: &DiceExpr) -> Self {
+        Distribution {
+            repr: Repr::Expr(expr.clone()),
+        }
+    }
+
+    /// Compute the exact probability mass function: a map from each
+    /// attainable sum to its probability.
+    pub fn pmf(&self) -> BTreeMap<i32, f64> {
+        match &self.repr {
+            Repr::Expr(expr) => expr.pmf(),
+            Repr::Bounds(dice) => {
+                let mut dist = BTreeMap::new();
+                dist.insert(0, 1.0);
+                for &(min, max) in dice {
+                    let faces = (max - min + 1) as f64;
+                    let mut next = BTreeMap::new();
+                    for (&sum, &p) in &dist {
+                        for face in min..=max {
+                            *next.entry(sum + face).or_insert(0.0) += p / faces;
+                        }
+                    }
+                    dist = next;
+                }
+                dist
+            }
+        }
+    }
+
+    /// The expected value (mean) of the sum.
+    pub fn mean(&self) -> f64 {
+        self.pmf().iter().map(|(&v, &p)| v as f64 * p).sum()
+    }
+
+    /// The variance of the sum.
+    pub fn variance(&self) -> f64 {
+        let mean = self.mean();
+        self.pmf()
+            .iter()
+            .map(|(&v, &p)| p * (v as f64 - mean).powi(2))
+            .sum()
+    }
+
+    /// The probability that the sum is at least `threshold`.
+    pub fn probability_at_least(&self, threshold: i32) -> f64 {
+        self.pmf()
+            .range(threshold..)
+            .map(|(_, &p)| p)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{d6, parse};
+
+    #[test]
+    fn single_d6_is_uniform() {
+        let dist = Distribution::new(&[d6()]);
+        let pmf = dist.pmf();
+        assert_eq!(pmf.len(), 6);
+        for p in pmf.values() {
+            assert!((p - 1.0 / 6.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn two_d6_pmf_sums_to_one() {
+        let dist = Distribution::new(&[d6(), d6()]);
+        let total: f64 = dist.pmf().values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!((dist.mean() - 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn probability_at_least_matches_pmf_tail() {
+        let dist = Distribution::new(&[d6(), d6()]);
+        let expected: f64 = dist.pmf().iter().filter(|&(&v, _)| v >= 10).map(|(_, &p)| p).sum();
+        assert!((dist.probability_at_least(10) - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn from_expr_accounts_for_flat_modifier() {
+        let expr = parse("3d6+2").unwrap();
+        let dist = Distribution::from_expr(&expr);
+        // 3d6 >= 12 (i.e. 3d6+2 >= 14) is 81/216 exactly.
+        assert!((dist.probability_at_least(14) - 81.0 / 216.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn from_expr_accounts_for_keep_highest() {
+        let expr = parse("2d20kh1").unwrap();
+        let dist = Distribution::from_expr(&expr);
+        assert!((dist.mean() - 13.825).abs() < 1e-9);
+    }
+}
@@ -1,10 +1,23 @@
-//! Simulate dice rolls and coin flips. For now, this relies on the default
-//! thread-local RNG provided by the `rand` crate.
+//! Simulate dice rolls and coin flips. The free functions in this module
+//! roll against a thread-local default RNG; see [`DiceRoller`] for a
+//! seedable, reproducible alternative.
 
 use rand::distributions::uniform::{SampleUniform, Uniform};
 use rand::prelude::*;
 // use rand::distributions::uniform::SampleUniform;
 
+mod roller;
+pub use roller::DiceRoller;
+
+mod weighted;
+pub use weighted::WeightedDie;
+
+mod notation;
+pub use notation::{parse, DiceExpr, ParseError, RollOutcome};
+
+mod distribution;
+pub use distribution::Distribution;
+
 /// Trait indicating a simulation of rolling some kind of die.
 ///
 /// Since dice may be marked with numbers, letters, or arbitrary
@@ -17,8 +30,7 @@ pub trait RollableDie<T> {
 
 /// Simulate a die roll using a reasonably good default PRNG.
 pub fn roll<T, D: RollableDie<T>>(d: &D) -> T {
-    let mut rng = thread_rng();
-    d.roll(&mut rng)
+    DiceRoller::thread_local().roll(d)
 }
 
 /// Simulate a number of rolls and return all of the results.
@@ -26,8 +38,28 @@ pub fn n_rolls<T, D>(n: usize, d: &D) -> Vec<T>
 where
     D: RollableDie<T>,
 {
-    let mut rng = thread_rng();
-    (0..n).map(|_| d.roll(&mut rng)).collect()
+    DiceRoller::thread_local().n_rolls(n, d)
+}
+
+/// Simulate an unbounded stream of rolls without allocating a `Vec`,
+/// analogous to `rand`'s `sample_iter`. Useful for feeding a histogram
+/// or taking only as many rolls as needed via `.take(k)`.
+///
+/// ```
+/// use rusty_dice::*;
+/// use rand::thread_rng;
+///
+/// let d = d6();
+/// let mut rng = thread_rng();
+/// let first_five: Vec<i32> = roll_iter(&d, &mut rng).take(5).collect();
+/// assert_eq!(first_five.len(), 5);
+/// ```
+pub fn roll_iter<'a, T, D, R>(d: &'a D, rng: &'a mut R) -> impl Iterator<Item = T> + 'a
+where
+    D: RollableDie<T> + 'a,
+    R: Rng,
+{
+    std::iter::from_fn(move || Some(d.roll(rng)))
 }
 
 /// Create a n-sided die numbered 1..n inclusive. Uses a signed
@@ -121,32 +153,59 @@ impl<T: Clone> GenericDie<T> {
             faces: iterator.into_iter().collect(),
         }
     }
+
+    /// Roll the die once per slot of `buf`, writing results directly
+    /// into it. For large batches this avoids the allocation that
+    /// `n_rolls` would otherwise perform.
+    pub fn fill_rolls<R: Rng>(&self, rng: &mut R, buf: &mut [T]) {
+        for slot in buf.iter_mut() {
+            *slot = self.roll(rng);
+        }
+    }
 }
 
 /// Represents a die whose faces are numbered n..m inclusive.
-pub struct RangeDie<T: SampleUniform> {
+pub struct RangeDie<T: SampleUniform + Clone> {
     faces: Uniform<T>,
+    min: T,
+    max: T,
 }
 
-impl<T: SampleUniform> RollableDie<T> for RangeDie<T> {
+impl<T: SampleUniform + Clone> RollableDie<T> for RangeDie<T> {
     fn roll<R: Rng>(&self, rng: &mut R) -> T {
         rng.sample(&self.faces)
     }
 }
 
-impl<T: SampleUniform> RollableDie<T> for &RangeDie<T> {
+impl<T: SampleUniform + Clone> RollableDie<T> for &RangeDie<T> {
     fn roll<R: Rng>(&self, rng: &mut R) -> T {
         rng.sample(&self.faces)
     }
 }
 
-impl<T: SampleUniform> RangeDie<T> {
+impl<T: SampleUniform + Clone> RangeDie<T> {
     /// Create a die with specified minimum and maximum values.
     pub fn new(min: T, max: T) -> Self {
         RangeDie {
-            faces: Uniform::new_inclusive(min, max),
+            faces: Uniform::new_inclusive(min.clone(), max.clone()),
+            min,
+            max,
+        }
+    }
+
+    /// Roll the die once per slot of `buf`, writing results directly
+    /// into it. For large batches this avoids the allocation that
+    /// `n_rolls` would otherwise perform.
+    pub fn fill_rolls<R: Rng>(&self, rng: &mut R, buf: &mut [T]) {
+        for slot in buf.iter_mut() {
+            *slot = self.roll(rng);
         }
     }
+
+    /// The inclusive minimum and maximum face values of this die.
+    pub fn bounds(&self) -> (T, T) {
+        (self.min.clone(), self.max.clone())
+    }
 }
 
 /// Represent coin flip results in a readable way.
@@ -158,11 +217,7 @@ pub enum CoinFace {
 
 /// Simulate coin flips.
 pub fn coin_flip() -> CoinFace {
-    match thread_rng().gen_range(0, 2) {
-        0 => CoinFace::Heads,
-        1 => CoinFace::Tails,
-        _ => unreachable!(),
-    }
+    DiceRoller::thread_local().coin_flip()
 }
 
 #[cfg(test)]
@@ -183,4 +238,48 @@ mod tests {
         assert_eq!(rolls.len(), 100);
         assert!(rolls.iter().sum::<i32>() >= 100);
     }
+
+    #[test]
+    fn seeded_roller_is_reproducible() {
+        let rolls_a: Vec<i32> = DiceRoller::seeded(42).n_rolls(50, &d20());
+        let rolls_b: Vec<i32> = DiceRoller::seeded(42).n_rolls(50, &d20());
+        assert_eq!(rolls_a, rolls_b);
+    }
+
+    #[test]
+    fn weighted_die_only_rolls_nonzero_faces() {
+        let loaded = WeightedDie::new([('a', 1), ('b', 0), ('c', 3)]).unwrap();
+        let mut roller = DiceRoller::seeded(7);
+        for _ in 0..1000 {
+            assert_ne!(roller.roll(&loaded), 'b');
+        }
+    }
+
+    #[test]
+    fn weighted_die_rejects_empty_faces() {
+        let faces: [(char, i32); 0] = [];
+        assert!(WeightedDie::new(faces).is_err());
+    }
+
+    #[test]
+    fn weighted_die_rejects_nan_weight() {
+        assert!(WeightedDie::new([('a', f64::NAN), ('b', 1.0)]).is_err());
+    }
+
+    #[test]
+    fn roll_iter_yields_rolls_lazily() {
+        let d = d6();
+        let mut rng = rand::rngs::SmallRng::seed_from_u64(3);
+        let rolls: Vec<i32> = roll_iter(&d, &mut rng).take(200).collect();
+        assert_eq!(rolls.len(), 200);
+        assert!(rolls.iter().all(|&v| (1..=6).contains(&v)));
+    }
+
+    #[test]
+    fn fill_rolls_overwrites_every_slot() {
+        let mut rng = thread_rng();
+        let mut buf = [0; 50];
+        d20().fill_rolls(&mut rng, &mut buf);
+        assert!(buf.iter().all(|&v| (1..=20).contains(&v)));
+    }
 }
@@ -0,0 +1,507 @@
+//! Parse standard tabletop dice notation ("3d6+2", "2d20kh1") into a
+//! `DiceExpr` that can be rolled directly, instead of hand-building
+//! `RangeDie`s and summing them.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use rand::Rng;
+
+use crate::{RangeDie, RollableDie};
+
+/// An error encountered while parsing a dice notation string.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    /// The expression ended before a complete term was parsed.
+    UnexpectedEnd,
+    /// An unexpected character was found while parsing.
+    UnexpectedChar(char),
+    /// A number could not be parsed where one was expected.
+    InvalidNumber(String),
+    /// A die was specified with zero or fewer sides.
+    InvalidDieSize,
+    /// A die pool was specified with zero dice.
+    InvalidDieCount,
+    /// A keep/drop modifier asked for zero dice, or for more dice than
+    /// the pool contains.
+    InvalidKeepCount,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd => write!(f, "unexpected end of expression"),
+            ParseError::UnexpectedChar(c) => write!(f, "unexpected character '{}'", c),
+            ParseError::InvalidNumber(s) => write!(f, "invalid number '{}'", s),
+            ParseError::InvalidDieSize => write!(f, "a die must have at least one side"),
+            ParseError::InvalidDieCount => write!(f, "a pool must contain at least one die"),
+            ParseError::InvalidKeepCount => {
+                write!(f, "cannot keep/drop zero dice or more dice than the pool contains")
+            }
+        }
+    }
+}
+
+/// The result of rolling a [`DiceExpr`]: the final total, plus the
+/// individual die results that produced it, in the order they were
+/// rolled -- so callers can display e.g. "rolled [4, 6, 2] -> 12".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RollOutcome {
+    pub total: i32,
+    pub rolls: Vec<i32>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum KeepRule {
+    Highest(usize),
+    Lowest(usize),
+    DropHighest(usize),
+    DropLowest(usize),
+}
+
+/// A recognized keep/drop suffix (`"kh"`, `"kl"`, ...) paired with the
+/// `KeepRule` variant constructor it should produce.
+type KeepTag = (&'static str, fn(usize) -> KeepRule);
+
+// `pub` (rather than `pub(crate)`) so that exposing it as a field of the
+// public `DiceExpr` enum below doesn't trip the `private_interfaces`
+// lint; its fields stay private, and `notation` itself is a private
+// module, so this isn't actually nameable outside the crate.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DicePool {
+    count: usize,
+    sides: i32,
+    keep: Option<KeepRule>,
+}
+
+impl DicePool {
+    fn roll<R: Rng>(&self, rng: &mut R) -> RollOutcome {
+        let die = RangeDie::new(1, self.sides);
+        let rolls: Vec<i32> = (0..self.count).map(|_| die.roll(rng)).collect();
+        let total = self.kept_sum(&rolls);
+        RollOutcome { total, rolls }
+    }
+
+    /// Apply this pool's keep/drop rule (if any) to a set of individual
+    /// die results and sum what's left.
+    fn kept_sum(&self, rolls: &[i32]) -> i32 {
+        match &self.keep {
+            None => rolls.iter().sum(),
+            Some(rule) => {
+                let mut sorted = rolls.to_vec();
+                sorted.sort_unstable();
+                let kept = match rule {
+                    KeepRule::Highest(k) => &sorted[sorted.len() - k..],
+                    KeepRule::Lowest(k) => &sorted[..*k],
+                    KeepRule::DropHighest(k) => &sorted[..sorted.len() - k],
+                    KeepRule::DropLowest(k) => &sorted[*k..],
+                };
+                kept.iter().sum()
+            }
+        }
+    }
+
+    /// Exact probability mass function for this pool's kept sum.
+    ///
+    /// Pools without a keep/drop rule are a plain convolution of `count`
+    /// uniform dice, same as the modifier-free path in `distribution.rs`.
+    /// Pools with a keep/drop rule need the distribution of an order
+    /// statistic, computed via `keep_highest_pmf`/`keep_lowest_pmf`
+    /// below rather than by enumerating all `sides ^ count` outcomes --
+    /// that enumeration overflows or hangs for anything but a handful of
+    /// dice (e.g. `20d6` is ~3.6e15 outcomes).
+    pub(crate) fn pmf(&self) -> BTreeMap<i32, f64> {
+        match &self.keep {
+            None => uniform_sum_pmf(self.count, self.sides),
+            Some(KeepRule::Highest(k)) => keep_highest_pmf(self.count, self.sides, *k),
+            Some(KeepRule::Lowest(k)) => keep_lowest_pmf(self.count, self.sides, *k),
+            Some(KeepRule::DropHighest(k)) => keep_lowest_pmf(self.count, self.sides, self.count - k),
+            Some(KeepRule::DropLowest(k)) => keep_highest_pmf(self.count, self.sides, self.count - k),
+        }
+    }
+}
+
+/// Exact pmf of the sum of `count` iid dice numbered `1..=sides`,
+/// computed by repeated convolution: O(count * sides * range).
+fn uniform_sum_pmf(count: usize, sides: i32) -> BTreeMap<i32, f64> {
+    let mut dist = BTreeMap::new();
+    dist.insert(0, 1.0);
+    for _ in 0..count {
+        let mut next = BTreeMap::new();
+        for (&sum, &p) in &dist {
+            for face in 1..=sides {
+                *next.entry(sum + face).or_insert(0.0) += p / sides as f64;
+            }
+        }
+        dist = next;
+    }
+    dist
+}
+
+/// Exact pmf of the sum of the `keep` highest of `count` iid dice
+/// numbered `1..=sides`.
+///
+/// Dice are interchangeable, so rather than enumerate all `sides ^
+/// count` orderings, this assigns dice to face values from highest to
+/// lowest: the DP state is `(dice_remaining, still_to_keep)`, and at
+/// each face value `v` it considers every possible count `c` of the
+/// remaining dice landing on `v`, weighted by the binomial coefficient
+/// for choosing which dice those are. Once `still_to_keep` reaches zero
+/// the rest of the dice (however they land) are dropped, so their face
+/// value no longer matters.
+fn keep_highest_pmf(count: usize, sides: i32, keep: usize) -> BTreeMap<i32, f64> {
+    let mut states: HashMap<(usize, usize), BTreeMap<i32, f64>> = HashMap::new();
+    states.insert((count, keep), BTreeMap::from([(0, 1.0)]));
+
+    for face in (1..=sides).rev() {
+        let mut next_states: HashMap<(usize, usize), BTreeMap<i32, f64>> = HashMap::new();
+        for (&(dice_remaining, still_to_keep), sums) in &states {
+            // On the last face value every remaining die is forced onto
+            // it; otherwise any count from 0 up to what's left is possible.
+            let choices: Vec<usize> = if face == 1 {
+                vec![dice_remaining]
+            } else {
+                (0..=dice_remaining).collect()
+            };
+            for landed_here in choices {
+                let ways = binomial(dice_remaining, landed_here);
+                let kept_here = still_to_keep.min(landed_here);
+                let next_state = (dice_remaining - landed_here, still_to_keep - kept_here);
+                let added = kept_here as i32 * face;
+                let next_sums = next_states.entry(next_state).or_default();
+                for (&sum, &p) in sums {
+                    *next_sums.entry(sum + added).or_insert(0.0) += p * ways;
+                }
+            }
+        }
+        states = next_states;
+    }
+
+    let counts = states.remove(&(0, 0)).unwrap_or_default();
+    let total: f64 = counts.values().sum();
+    counts.into_iter().map(|(sum, c)| (sum, c / total)).collect()
+}
+
+/// Exact pmf of the sum of the `keep` lowest of `count` iid dice
+/// numbered `1..=sides`, obtained by mirroring each face value (`v` ->
+/// `sides + 1 - v`) and reusing `keep_highest_pmf`: the lowest-valued
+/// dice under the original numbering are the highest-valued under the
+/// mirrored one.
+fn keep_lowest_pmf(count: usize, sides: i32, keep: usize) -> BTreeMap<i32, f64> {
+    let mirrored_total = keep as i32 * (sides + 1);
+    keep_highest_pmf(count, sides, keep)
+        .into_iter()
+        .map(|(sum, p)| (mirrored_total - sum, p))
+        .collect()
+}
+
+/// `n choose k`, computed iteratively as a float to avoid overflowing
+/// factorials for larger dice pools.
+fn binomial(n: usize, k: usize) -> f64 {
+    if k > n {
+        return 0.0;
+    }
+    let k = k.min(n - k);
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+/// A parsed dice-notation expression, ready to be rolled repeatedly.
+/// Build one with [`parse`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DiceExpr {
+    Constant(i32),
+    Dice(DicePool),
+    Add(Box<DiceExpr>, Box<DiceExpr>),
+    Sub(Box<DiceExpr>, Box<DiceExpr>),
+    Mul(Box<DiceExpr>, Box<DiceExpr>),
+}
+
+impl DiceExpr {
+    /// Roll the expression, returning both its total and the individual
+    /// die results that produced it.
+    pub fn roll<R: Rng>(&mut self, rng: &mut R) -> RollOutcome {
+        match self {
+            DiceExpr::Constant(n) => RollOutcome {
+                total: *n,
+                rolls: Vec::new(),
+            },
+            DiceExpr::Dice(pool) => pool.roll(rng),
+            DiceExpr::Add(lhs, rhs) => merge(lhs.roll(rng), rhs.roll(rng), |a, b| a + b),
+            DiceExpr::Sub(lhs, rhs) => merge(lhs.roll(rng), rhs.roll(rng), |a, b| a - b),
+            DiceExpr::Mul(lhs, rhs) => merge(lhs.roll(rng), rhs.roll(rng), |a, b| a * b),
+        }
+    }
+
+    /// Exact probability mass function for the whole expression,
+    /// computed by convolving each node's distribution from the leaves
+    /// up. Dice pools account for any keep/drop rule exactly (not just
+    /// their unmodified sum), so this also answers questions like
+    /// "what's the chance 2d20kh1 beats 15?"
+    pub(crate) fn pmf(&self) -> BTreeMap<i32, f64> {
+        match self {
+            DiceExpr::Constant(n) => {
+                let mut dist = BTreeMap::new();
+                dist.insert(*n, 1.0);
+                dist
+            }
+            DiceExpr::Dice(pool) => pool.pmf(),
+            DiceExpr::Add(lhs, rhs) => convolve(&lhs.pmf(), &rhs.pmf(), |a, b| a + b),
+            DiceExpr::Sub(lhs, rhs) => convolve(&lhs.pmf(), &rhs.pmf(), |a, b| a - b),
+            DiceExpr::Mul(lhs, rhs) => convolve(&lhs.pmf(), &rhs.pmf(), |a, b| a * b),
+        }
+    }
+}
+
+fn merge(mut lhs: RollOutcome, rhs: RollOutcome, op: impl Fn(i32, i32) -> i32) -> RollOutcome {
+    let total = op(lhs.total, rhs.total);
+    lhs.rolls.extend(rhs.rolls);
+    RollOutcome {
+        total,
+        rolls: lhs.rolls,
+    }
+}
+
+/// Combine two independent probability mass functions by applying `op`
+/// to every pair of attainable values, summing probabilities that
+/// collide on the same result.
+fn convolve(
+    lhs: &BTreeMap<i32, f64>,
+    rhs: &BTreeMap<i32, f64>,
+    op: impl Fn(i32, i32) -> i32,
+) -> BTreeMap<i32, f64> {
+    let mut result = BTreeMap::new();
+    for (&a, &pa) in lhs {
+        for (&b, &pb) in rhs {
+            *result.entry(op(a, b)).or_insert(0.0) += pa * pb;
+        }
+    }
+    result
+}
+
+/// Parse standard dice notation, e.g. `"3d6+2"`, `"2d20kh1"`, `"adv"`.
+///
+/// Supports `NdM` dice pools, flat `+`/`-` modifiers, `*` for scaling,
+/// keep-highest/lowest (`kh`/`kl`) and drop-highest/lowest (`dh`/`dl`)
+/// pool operators, and `adv`/`dis` as sugar for `2d20kh1`/`2d20kl1`.
+///
+/// ```
+/// use rusty_dice::parse;
+/// use rand::thread_rng;
+///
+/// let mut attack = parse("1d20+5").unwrap();
+/// let outcome = attack.roll(&mut thread_rng());
+/// println!("rolled {:?} -> {}", outcome.rolls, outcome.total);
+/// ```
+pub fn parse(expr: &str) -> Result<DiceExpr, ParseError> {
+    let trimmed = expr.trim().to_ascii_lowercase();
+    match trimmed.as_str() {
+        "adv" | "advantage" => return parse("2d20kh1"),
+        "dis" | "disadvantage" => return parse("2d20kl1"),
+        _ => {}
+    }
+
+    let mut parser = Parser {
+        chars: trimmed.chars().peekable(),
+    };
+    let result = parser.parse_expr()?;
+    match parser.chars.peek() {
+        None => Ok(result),
+        Some(&c) => Err(ParseError::UnexpectedChar(c)),
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    fn parse_expr(&mut self) -> Result<DiceExpr, ParseError> {
+        let mut node = self.parse_term()?;
+        loop {
+            match self.chars.peek() {
+                Some('+') => {
+                    self.chars.next();
+                    let rhs = self.parse_term()?;
+                    node = DiceExpr::Add(Box::new(node), Box::new(rhs));
+                }
+                Some('-') => {
+                    self.chars.next();
+                    let rhs = self.parse_term()?;
+                    node = DiceExpr::Sub(Box::new(node), Box::new(rhs));
+                }
+                _ => break,
+            }
+        }
+        Ok(node)
+    }
+
+    fn parse_term(&mut self) -> Result<DiceExpr, ParseError> {
+        let mut node = self.parse_factor()?;
+        while let Some(&'*') = self.chars.peek() {
+            self.chars.next();
+            let rhs = self.parse_factor()?;
+            node = DiceExpr::Mul(Box::new(node), Box::new(rhs));
+        }
+        Ok(node)
+    }
+
+    fn parse_factor(&mut self) -> Result<DiceExpr, ParseError> {
+        let leading_number = self.parse_number()?;
+        if let Some(&'d') = self.chars.peek() {
+            self.chars.next();
+            let sides = self.parse_number()?;
+            if sides < 1 {
+                return Err(ParseError::InvalidDieSize);
+            }
+            if leading_number < 1 {
+                return Err(ParseError::InvalidDieCount);
+            }
+            let count = leading_number as usize;
+            let keep = self.parse_keep_rule(count)?;
+            Ok(DiceExpr::Dice(DicePool { count, sides, keep }))
+        } else {
+            Ok(DiceExpr::Constant(leading_number))
+        }
+    }
+
+    fn parse_keep_rule(&mut self, count: usize) -> Result<Option<KeepRule>, ParseError> {
+        let tags: [KeepTag; 4] = [
+            ("kh", KeepRule::Highest),
+            ("kl", KeepRule::Lowest),
+            ("dh", KeepRule::DropHighest),
+            ("dl", KeepRule::DropLowest),
+        ];
+        for (tag, make) in tags {
+            if self.peek_str(tag) {
+                for _ in 0..tag.len() {
+                    self.chars.next();
+                }
+                let k = self.parse_optional_number()?.unwrap_or(1) as usize;
+                if k == 0 || k > count {
+                    return Err(ParseError::InvalidKeepCount);
+                }
+                return Ok(Some(make(k)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn peek_str(&self, s: &str) -> bool {
+        let mut iter = self.chars.clone();
+        for expected in s.chars() {
+            match iter.next() {
+                Some(c) if c == expected => continue,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    fn parse_number(&mut self) -> Result<i32, ParseError> {
+        match self.parse_optional_number()? {
+            Some(n) => Ok(n),
+            None => match self.chars.peek() {
+                Some(&c) => Err(ParseError::UnexpectedChar(c)),
+                None => Err(ParseError::UnexpectedEnd),
+            },
+        }
+    }
+
+    fn parse_optional_number(&mut self) -> Result<Option<i32>, ParseError> {
+        let mut digits = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        if digits.is_empty() {
+            return Ok(None);
+        }
+        digits
+            .parse::<i32>()
+            .map(Some)
+            .map_err(|_| ParseError::InvalidNumber(digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn parses_flat_modifier() {
+        let mut expr = parse("3d6+2").unwrap();
+        let mut rng = SmallRng::seed_from_u64(1);
+        let outcome = expr.roll(&mut rng);
+        assert_eq!(outcome.rolls.len(), 3);
+        assert_eq!(outcome.total, outcome.rolls.iter().sum::<i32>() + 2);
+    }
+
+    #[test]
+    fn keep_highest_drops_the_rest() {
+        let mut expr = parse("2d20kh1").unwrap();
+        let mut rng = SmallRng::seed_from_u64(2);
+        let outcome = expr.roll(&mut rng);
+        assert_eq!(outcome.rolls.len(), 2);
+        assert_eq!(outcome.total, *outcome.rolls.iter().max().unwrap());
+    }
+
+    #[test]
+    fn advantage_is_sugar_for_2d20kh1() {
+        assert_eq!(parse("adv").unwrap(), parse("2d20kh1").unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        assert_eq!(parse("0d6"), Err(ParseError::InvalidDieCount));
+        assert_eq!(parse("3d0"), Err(ParseError::InvalidDieSize));
+        assert_eq!(parse("xd6"), Err(ParseError::UnexpectedChar('x')));
+    }
+
+    #[test]
+    fn pmf_matches_brute_force_for_small_keep_pool() {
+        // 3d6kh2: brute force all 216 orderings and compare against the
+        // order-statistics DP.
+        let expr = parse("3d6kh2").unwrap();
+        let dp = expr.pmf();
+
+        let mut brute: BTreeMap<i32, f64> = BTreeMap::new();
+        for a in 1..=6 {
+            for b in 1..=6 {
+                for c in 1..=6 {
+                    let mut sorted = [a, b, c];
+                    sorted.sort_unstable();
+                    let kept: i32 = sorted[1..].iter().sum();
+                    *brute.entry(kept).or_insert(0.0) += 1.0 / 216.0;
+                }
+            }
+        }
+
+        assert_eq!(dp.len(), brute.len());
+        for (sum, &p) in &brute {
+            assert!((dp[sum] - p).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn pmf_handles_large_pools_without_hanging() {
+        // Large enough that brute-force enumeration (sides^count) would
+        // overflow or hang; the DP/convolution path stays fast.
+        let plain = parse("20d6").unwrap();
+        let dist = plain.pmf();
+        let total: f64 = dist.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        let with_keep = parse("64d2kh32").unwrap();
+        let dist = with_keep.pmf();
+        let total: f64 = dist.values().sum();
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,68 @@
+//! A stateful dice roller that owns its own RNG, so results can be
+//! replayed or made deterministic instead of always drawing from the
+//! thread-local default.
+
+use rand::rngs::{SmallRng, ThreadRng};
+use rand::{thread_rng, Rng, SeedableRng};
+
+use crate::{CoinFace, RollableDie};
+
+/// Rolls dice and flips coins against a single owned RNG.
+///
+/// Unlike the free functions [`crate::roll`], [`crate::n_rolls`], and
+/// [`crate::coin_flip`], which each draw from the thread-local RNG,
+/// a `DiceRoller` can be seeded to produce a reproducible sequence of
+/// results -- useful for tests, game replays, or sharing a seed so two
+/// players see the same table of rolls.
+pub struct DiceRoller<R: Rng> {
+    rng: R,
+}
+
+impl<R: Rng> DiceRoller<R> {
+    /// Wrap an existing RNG in a `DiceRoller`.
+    pub fn new(rng: R) -> Self {
+        DiceRoller { rng }
+    }
+
+    /// Simulate a single roll of the given die.
+    pub fn roll<T, D: RollableDie<T>>(&mut self, d: &D) -> T {
+        d.roll(&mut self.rng)
+    }
+
+    /// Simulate a number of rolls and return all of the results.
+    pub fn n_rolls<T, D: RollableDie<T>>(&mut self, n: usize, d: &D) -> Vec<T> {
+        (0..n).map(|_| d.roll(&mut self.rng)).collect()
+    }
+
+    /// Simulate a coin flip.
+    pub fn coin_flip(&mut self) -> CoinFace {
+        match self.rng.gen_range(0, 2) {
+            0 => CoinFace::Heads,
+            1 => CoinFace::Tails,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl DiceRoller<SmallRng> {
+    /// Create a roller seeded for reproducible results. The same seed
+    /// always produces the same sequence of rolls.
+    pub fn seeded(seed: u64) -> Self {
+        DiceRoller::new(SmallRng::seed_from_u64(seed))
+    }
+
+    /// Create a roller seeded from the system's entropy source. Results
+    /// are not reproducible, but construction is cheaper than repeatedly
+    /// drawing from the thread-local RNG.
+    pub fn from_entropy() -> Self {
+        DiceRoller::new(SmallRng::from_entropy())
+    }
+}
+
+impl DiceRoller<ThreadRng> {
+    /// Create a roller backed by the thread-local default RNG. Used
+    /// internally by the free-function wrappers in the crate root.
+    pub(crate) fn thread_local() -> Self {
+        DiceRoller::new(thread_rng())
+    }
+}
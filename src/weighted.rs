@@ -0,0 +1,108 @@
+//! Loaded (weighted) dice, sampled in O(1) per roll via Vose's alias
+//! method.
+
+use rand::Rng;
+
+use crate::RollableDie;
+
+/// A die whose faces are not equally likely. Useful for biased dice or
+/// "averaging" dice that bunch results toward the middle of a range.
+///
+/// Internally this builds an alias table once at construction time, so
+/// repeated rolls are O(1) regardless of how skewed the weights are.
+#[derive(Clone, Debug)]
+pub struct WeightedDie<T: Clone> {
+    faces: Vec<T>,
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl<T: Clone> WeightedDie<T> {
+    /// Build a weighted die from `(face, weight)` pairs. Weights may be
+    /// given as integers or floats (anything convertible to `f64`); only
+    /// their ratios matter, so they need not sum to any particular
+    /// total. Returns an error if there are no faces or if any weight is
+    /// negative or all weights are zero.
+    pub fn new<I, W>(weighted_faces: I) -> Result<Self, &'static str>
+    where
+        I: IntoIterator<Item = (T, W)>,
+        W: Into<f64>,
+    {
+        let (faces, weights): (Vec<T>, Vec<f64>) = weighted_faces
+            .into_iter()
+            .map(|(face, weight)| (face, weight.into()))
+            .unzip();
+
+        let n = faces.len();
+        if n == 0 {
+            return Err("A weighted die must have at least one face.");
+        }
+        if weights.iter().any(|&w| w.is_nan() || w < 0.0) {
+            return Err("Face weights must not be negative or NaN.");
+        }
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return Err("Face weights must sum to a positive total.");
+        }
+
+        // Vose's alias method: scale each weight so the average is 1,
+        // then repeatedly pair an under-weighted face with an
+        // over-weighted one until every face's probability mass is
+        // accounted for by itself or its alias.
+        let mut scaled: Vec<f64> = weights.iter().map(|&w| n as f64 * w / total).collect();
+        let mut prob = vec![0.0; n];
+        let mut alias = vec![0; n];
+
+        let mut small: Vec<usize> = Vec::new();
+        let mut large: Vec<usize> = Vec::new();
+        for (i, &p) in scaled.iter().enumerate() {
+            if p < 1.0 {
+                small.push(i);
+            } else {
+                large.push(i);
+            }
+        }
+
+        while let Some(l) = small.pop() {
+            let g = match large.pop() {
+                Some(g) => g,
+                None => {
+                    // Floating-point slack, not a real shortfall: `l`
+                    // resolves to itself below.
+                    small.push(l);
+                    break;
+                }
+            };
+            prob[l] = scaled[l];
+            alias[l] = g;
+            scaled[g] -= 1.0 - scaled[l];
+            if scaled[g] < 1.0 {
+                small.push(g);
+            } else {
+                large.push(g);
+            }
+        }
+        // Leftover indices accumulated floating-point slack rather than
+        // a real shortfall; they always resolve to themselves.
+        for i in large.into_iter().chain(small) {
+            prob[i] = 1.0;
+        }
+
+        Ok(WeightedDie { faces, prob, alias })
+    }
+}
+
+impl<T: Clone> RollableDie<T> for WeightedDie<T> {
+    fn roll<R: Rng>(&self, rng: &mut R) -> T {
+        let i = rng.gen_range(0, self.faces.len());
+        let u: f64 = rng.gen();
+        let face = if u < self.prob[i] { i } else { self.alias[i] };
+        self.faces[face].clone()
+    }
+}
+
+impl<T: Clone> RollableDie<T> for &WeightedDie<T> {
+    fn roll<R: Rng>(&self, rng: &mut R) -> T {
+        (*self).roll(rng)
+    }
+}